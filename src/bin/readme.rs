@@ -25,20 +25,21 @@ Example code adapted from the README.md of
 [the regex-chunker crate](https://github.com/d2718/regex-chunker).
 */
 use regex::bytes::Regex;
-use regex_splitter::LendingIterator;
 use std::error::Error;
 
 fn example() -> Result<(), Box<dyn Error>> {
-    use regex_splitter::RegexSplitter;
+    use regex_splitter::RegexSplitterBuilder;
     use std::collections::BTreeMap;
 
     let mut counts: BTreeMap<String, usize> = BTreeMap::new();
 
     let mut stdin = std::io::stdin();
     let re = Regex::new(r#"[ "\r\n.,!?:;/]+"#)?;
-    let mut chunker = RegexSplitter::new(&mut stdin, &re);
+    let chunker = RegexSplitterBuilder::new()
+        .skip_empty(true)
+        .build(&mut stdin, &re);
 
-    while let Some(chunk) = chunker.next() {
+    for chunk in chunker.owned() {
         let word = String::from_utf8_lossy(&chunk?).to_lowercase();
         *counts.entry(word).or_default() += 1;
     }
@@ -4,15 +4,22 @@
 //! An `Iterator` that yields `Vec<u8>`s from `Read`s, delimited by regular
 //! expressions.
 
-use std::io::{Read, Result};
+use std::fmt;
+use std::io::{Error, Read, Result};
 
-use regex::bytes::Regex;
+use regex::bytes::{Captures, Regex};
 
 /// An `Iterator` that scans a `Read`, searches for the `delimiter`, and yields
 /// the non-delimiter bytes.
 ///
 /// This implementation uses a private buffer that will grow proportional to the
-/// largest span of bytes between instances of `delimiter`.
+/// largest span of bytes between instances of `delimiter`, unless constructed
+/// with [`RegexSplitter::with_max_capacity`], in which case it grows only up to
+/// the given limit.
+///
+/// To configure more than one of capacity, maximum capacity, maximum
+/// delimiter length, and empty-record suppression at once, use
+/// [`RegexSplitterBuilder`] instead of the `with_*` constructors.
 pub struct RegexSplitter<'a, 'b> {
     reader: &'a mut dyn Read,
     delimiter: &'b Regex,
@@ -22,11 +29,63 @@ pub struct RegexSplitter<'a, 'b> {
     start: usize,
     end: usize,
     eof: bool,
+    // The buffer will not be grown past this size. `None` means unbounded.
+    max_capacity: Option<usize>,
+    // How far into `buffer[start..end]` we have already confirmed contains no
+    // delimiter match. Used to avoid rescanning already-searched bytes from
+    // scratch; see `max_delimiter_len`.
+    searched: usize,
+    // An upper bound on the byte length of any delimiter match, used to
+    // narrow the range rescanned on each `fill`. `None` means every search
+    // rescans `buffer[start..end]` in full.
+    max_delimiter_len: Option<usize>,
+    // If true, zero-length records (from a leading delimiter or consecutive
+    // delimiters) are skipped internally rather than yielded.
+    skip_empty: bool,
 }
 
 /// This value is arbitrary, but seems good enough.
 pub const DEFAULT_CAPACITY: usize = 64 * 1024;
 
+// The `buffer` range of a record, and, if a delimiter terminated it, the
+// `buffer` range of that delimiter together with the offset the delimiter
+// search started from. Returned by the private `advance`, which backs
+// `LendingIterator::next`, `next_with_delimiter`, and `next_with_captures`.
+// The search-start offset is threaded through so `next_with_captures` can
+// re-run `Regex::captures` against the same haystack `find` matched
+// against, rather than an isolated slice that may be missing the context a
+// delimiter like `\b` depends on.
+type Advanced = (usize, usize, Option<(usize, usize, usize)>);
+
+/// A record and, if a delimiter terminated it, the delimiter bytes that did
+/// so. Returned by [`RegexSplitter::next_with_delimiter`].
+pub type RecordAndDelimiter<'a> = (&'a [u8], Option<&'a [u8]>);
+
+/// The error returned when a record requires the buffer to grow past the
+/// `max_capacity` given to [`RegexSplitter::with_max_capacity`].
+#[derive(Debug)]
+pub struct RecordTooLarge {
+    pub max_capacity: usize,
+}
+
+impl fmt::Display for RecordTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "record exceeded the maximum buffer capacity of {} bytes",
+            self.max_capacity
+        )
+    }
+}
+
+impl std::error::Error for RecordTooLarge {}
+
+impl From<RecordTooLarge> for Error {
+    fn from(error: RecordTooLarge) -> Self {
+        Error::other(error)
+    }
+}
+
 impl<'a, 'b> RegexSplitter<'a, 'b> {
     /// Returns a new `StreamSplitter` that will split the bytes of `reader`
     /// into `Vec<u8>`s.
@@ -45,43 +104,124 @@ impl<'a, 'b> RegexSplitter<'a, 'b> {
             start: 0,
             end: 0,
             eof: false,
+            max_capacity: None,
+            searched: 0,
+            max_delimiter_len: None,
+            skip_empty: false,
+        }
+    }
+
+    /// Returns a new `StreamSplitter` whose internal buffer starts at
+    /// `initial` bytes and is never grown past `max` bytes. If a record would
+    /// require growing the buffer past `max`, `next` yields an `Err` wrapping
+    /// [`RecordTooLarge`] instead of growing it without bound. This makes
+    /// it safe to point the splitter at untrusted input.
+    pub fn with_max_capacity(
+        reader: &'a mut dyn Read,
+        delimiter: &'b Regex,
+        initial: usize,
+        max: usize,
+    ) -> Self {
+        Self {
+            reader,
+            delimiter,
+            buffer: vec![0; initial.min(max)],
+            start: 0,
+            end: 0,
+            eof: false,
+            max_capacity: Some(max),
+            searched: 0,
+            max_delimiter_len: None,
+            skip_empty: false,
+        }
+    }
+
+    /// Returns a new `StreamSplitter` that will split the bytes of `reader`
+    /// into `Vec<u8>`s, and that bounds any byte match of `delimiter` to at
+    /// most `max_delimiter_len` bytes. Knowing this bound lets the splitter
+    /// avoid rescanning the entire accumulated section on each `fill` when a
+    /// record spans many buffer-fulls of data, so finding one large record
+    /// costs time linear, rather than quadratic, in its length. Without this
+    /// bound (see `with_capacity`), every `fill` rescans the section from the
+    /// start.
+    pub fn with_max_delimiter_len(
+        reader: &'a mut dyn Read,
+        delimiter: &'b Regex,
+        capacity: usize,
+        max_delimiter_len: usize,
+    ) -> Self {
+        Self {
+            reader,
+            delimiter,
+            buffer: vec![0; capacity],
+            start: 0,
+            end: 0,
+            eof: false,
+            max_capacity: None,
+            searched: 0,
+            max_delimiter_len: Some(max_delimiter_len),
+            skip_empty: false,
         }
     }
 
-    /// Fills the `StreamSplitter`’s buffer, growing it if it is already full.
+    /// Fills the `StreamSplitter`’s buffer. If it is already full, first moves
+    /// the unconsumed bytes to the front, or, if there are no consumed bytes
+    /// to reclaim, grows it when not yet at `max_capacity`.
     fn fill(&mut self) -> Result<()> {
-        if self.end == self.buffer.capacity() {
+        // `Vec::capacity()` is only a lower bound on the allocation; the
+        // allocator is free to hand back more than we asked `resize` for.
+        // `self.buffer.len()` is the size we actually intend to fill, so it
+        // (not `capacity()`) is what `self.end` is measured against.
+        if self.end == self.buffer.len() {
             if self.start == self.end {
                 // We have consumed the buffer. Reset it:
                 self.start = 0;
                 self.end = 0;
+                self.searched = 0;
+            } else if self.start > 0 {
+                // Part of the buffer holds already-consumed records. Move the
+                // unconsumed bytes to the front to make room, before growing:
+                self.buffer.copy_within(self.start..self.end, 0);
+                self.end -= self.start;
+                self.searched = self.searched.saturating_sub(self.start);
+                self.start = 0;
+            } else if let Some(max_capacity) = self.max_capacity {
+                if self.buffer.len() >= max_capacity {
+                    return Err(RecordTooLarge { max_capacity }.into());
+                }
+                // The buffer is full, but not yet at its limit. Grow it, but
+                // not past the limit:
+                let capacity = (2 * self.buffer.len()).min(max_capacity);
+                self.buffer.resize(capacity, 0);
             } else {
                 // The buffer is full. To read more, we must grow it:
-                self.buffer.resize(2 * self.buffer.capacity(), 0);
+                self.buffer.resize(2 * self.buffer.len(), 0);
             }
         }
-        let cap = self.buffer.capacity();
-        let n = self.reader.read(&mut self.buffer[self.end..cap])?;
+        let len = self.buffer.len();
+        let n = self.reader.read(&mut self.buffer[self.end..len])?;
         self.end += n;
         if n == 0 {
             self.eof = true;
         }
         Ok(())
     }
-}
-
-pub trait LendingIterator {
-    type Item<'a>
-    where
-        Self: 'a;
 
-    fn next<'a>(&'a mut self) -> Option<Self::Item<'a>>;
-}
-
-impl<'a, 'b> LendingIterator for RegexSplitter<'a, 'b> {
-    type Item<'c> = Result<&'c [u8]> where Self: 'c;
+    /// Returns an adapter that implements `std::iter::Iterator<Item =
+    /// Result<Vec<u8>>>`, for callers who want to use `for` loops and the
+    /// standard combinator ecosystem (`map`, `filter`, `collect`, `flatten`)
+    /// at the cost of copying each record into an owned `Vec<u8>`.
+    pub fn owned(self) -> OwnedRegexSplitter<'a, 'b> {
+        OwnedRegexSplitter { splitter: self }
+    }
 
-    fn next<'c>(&'c mut self) -> Option<Self::Item<'c>> {
+    /// Finds the next record, advancing `self.start` past it and its
+    /// delimiter (if any). Returns the end-exclusive `buffer` range of the
+    /// record, and, if a delimiter terminated it, the end-exclusive `buffer`
+    /// range of the delimiter. This is the shared implementation behind
+    /// [`LendingIterator::next`], [`RegexSplitter::next_with_delimiter`], and
+    /// [`RegexSplitter::next_with_captures`].
+    fn advance(&mut self) -> Option<Result<Advanced>> {
         loop {
             if let Err(error) = self.fill() {
                 return Some(Err(error));
@@ -91,30 +231,220 @@ impl<'a, 'b> LendingIterator for RegexSplitter<'a, 'b> {
                 return None;
             }
 
-            let section = &self.buffer[self.start..self.end];
+            let record_start = self.start;
+            // Bytes before `searched - max_delimiter_len` cannot be part of a
+            // match we haven't already ruled out: any match starting earlier
+            // would have ended by `searched` at the latest, and we already
+            // confirmed no match in `buffer[start..searched]`. So it is safe
+            // to search from there instead of rescanning the whole section.
+            let search_from = match self.max_delimiter_len {
+                Some(max_delimiter_len) => self
+                    .start
+                    .max(self.searched.saturating_sub(max_delimiter_len)),
+                None => self.start,
+            };
+            let section = &self.buffer[search_from..self.end];
             if let Some(m) = self.delimiter.find(section) {
-                if self.start + m.end() == self.end && !self.eof {
+                let delimiter_start = search_from + m.start();
+                let delimiter_end = search_from + m.end();
+                if delimiter_end == self.end && !self.eof {
                     // `self.buffer` ends in delimiter-matching bytes, yet we
                     // are not at EOF. So we might not have matched the
                     // entirety of the delimiter. Therefore, start back at the
                     // top, which incurs a `fill`, which will grow
                     // `self.buffer`. The `unwrap` is OK because we must at
-                    // least match the same match again.
+                    // least match the same match again. Remember we already
+                    // ruled out a match before `delimiter_start`.
+                    self.searched = delimiter_start;
                     continue;
                 }
-                self.start += m.end();
-                let r = if m.start() == 0 {
-                    // We matched the delimiter at the beginning of the section.
-                    Ok(&section[0..0])
-                } else {
-                    // We matched a record.
-                    Ok(&section[0..m.start()])
-                };
-                return Some(r);
-            } else {
+                self.start = delimiter_end;
+                self.searched = self.start;
+                if self.skip_empty && record_start == delimiter_start {
+                    // A leading or repeated delimiter produced a zero-length
+                    // record; loop past it instead of surfacing it.
+                    continue;
+                }
+                return Some(Ok((
+                    record_start,
+                    delimiter_start,
+                    Some((delimiter_start, delimiter_end, search_from)),
+                )));
+            } else if self.eof {
                 // Last record, with no trailing delimiter.
+                self.searched = self.end;
                 self.start = self.end;
-                return Some(Ok(section));
+                return Some(Ok((record_start, self.end, None)));
+            } else {
+                // No match yet, but there may be more data to come that
+                // completes one. Remember we've ruled out a match up to
+                // `end`, and loop back to `fill` more.
+                self.searched = self.end;
+                continue;
+            }
+        }
+    }
+
+    /// Like [`LendingIterator::next`], but also yields the delimiter bytes
+    /// that ended the record. The delimiter is `None` only for the final
+    /// record, which has no trailing delimiter. This is useful for formats
+    /// where the separator itself carries information (for example, MIME
+    /// multipart boundaries), since it lets callers reconstruct the original
+    /// stream byte-for-byte and branch on which separator fired. With
+    /// [`RegexSplitterBuilder::skip_empty`] enabled, the delimiters that ended
+    /// skipped zero-length records are not yielded, so the stream can no
+    /// longer be reconstructed from the results.
+    pub fn next_with_delimiter(&mut self) -> Option<Result<RecordAndDelimiter<'_>>> {
+        match self.advance() {
+            None => None,
+            Some(Err(error)) => Some(Err(error)),
+            Some(Ok((record_start, record_end, delimiter))) => Some(Ok((
+                &self.buffer[record_start..record_end],
+                delimiter.map(|(start, end, _)| &self.buffer[start..end]),
+            ))),
+        }
+    }
+
+    /// Like [`RegexSplitter::next_with_delimiter`], but also yields the
+    /// `regex::bytes::Captures` of the matched delimiter, for delimiters with
+    /// capture groups.
+    pub fn next_with_captures(&mut self) -> Option<Result<(&[u8], Option<Captures<'_>>)>> {
+        match self.advance() {
+            None => None,
+            Some(Err(error)) => Some(Err(error)),
+            Some(Ok((record_start, record_end, delimiter))) => {
+                let captures = delimiter.map(|(_, _, search_from)| {
+                    // Capture against the same haystack `advance` searched
+                    // (`search_from..end`), not just the delimiter's own
+                    // matched span: a delimiter like `\b,` depends on the
+                    // byte before its match, which an isolated slice of just
+                    // the match itself wouldn't contain. The `expect` is OK
+                    // because we must at least match the same match again.
+                    self.delimiter
+                        .captures(&self.buffer[search_from..self.end])
+                        .expect("delimiter must match again in the same haystack")
+                });
+                Some(Ok((&self.buffer[record_start..record_end], captures)))
+            }
+        }
+    }
+}
+
+/// A builder for [`RegexSplitter`], for configuring more than one of
+/// `capacity`, `max_capacity`, `max_delimiter_len`, and `skip_empty` at once.
+///
+/// ```no_run
+/// # use regex::bytes::Regex;
+/// # use regex_splitter::RegexSplitterBuilder;
+/// # let mut reader = std::io::empty();
+/// let delimiter = Regex::new(r"\s+").unwrap();
+/// let splitter = RegexSplitterBuilder::new()
+///     .max_capacity(1024 * 1024)
+///     .skip_empty(true)
+///     .build(&mut reader, &delimiter);
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RegexSplitterBuilder {
+    capacity: Option<usize>,
+    max_capacity: Option<usize>,
+    max_delimiter_len: Option<usize>,
+    skip_empty: bool,
+}
+
+impl RegexSplitterBuilder {
+    /// Returns a new `RegexSplitterBuilder` with no options set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the initial buffer capacity. Defaults to `DEFAULT_CAPACITY`.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Sets the maximum buffer capacity; see
+    /// [`RegexSplitter::with_max_capacity`].
+    pub fn max_capacity(mut self, max_capacity: usize) -> Self {
+        self.max_capacity = Some(max_capacity);
+        self
+    }
+
+    /// Sets an upper bound on the byte length of any delimiter match; see
+    /// [`RegexSplitter::with_max_delimiter_len`].
+    pub fn max_delimiter_len(mut self, max_delimiter_len: usize) -> Self {
+        self.max_delimiter_len = Some(max_delimiter_len);
+        self
+    }
+
+    /// If `true`, collapses a leading delimiter and consecutive delimiters by
+    /// looping past the zero-length records they produce, instead of
+    /// surfacing them, the way `awk`/`split`-style tools treat repeated field
+    /// separators.
+    pub fn skip_empty(mut self, skip_empty: bool) -> Self {
+        self.skip_empty = skip_empty;
+        self
+    }
+
+    /// Builds a [`RegexSplitter`] that reads from `reader` and splits on
+    /// `delimiter`, according to this builder's configuration.
+    pub fn build<'a, 'b>(
+        self,
+        reader: &'a mut dyn Read,
+        delimiter: &'b Regex,
+    ) -> RegexSplitter<'a, 'b> {
+        let capacity = self.capacity.unwrap_or(DEFAULT_CAPACITY);
+        let capacity = match self.max_capacity {
+            Some(max_capacity) => capacity.min(max_capacity),
+            None => capacity,
+        };
+        RegexSplitter {
+            reader,
+            delimiter,
+            buffer: vec![0; capacity],
+            start: 0,
+            end: 0,
+            eof: false,
+            max_capacity: self.max_capacity,
+            searched: 0,
+            max_delimiter_len: self.max_delimiter_len,
+            skip_empty: self.skip_empty,
+        }
+    }
+}
+
+/// An owning adapter over a [`RegexSplitter`] that implements
+/// `std::iter::Iterator<Item = Result<Vec<u8>>>`. Returned by
+/// [`RegexSplitter::owned`].
+pub struct OwnedRegexSplitter<'a, 'b> {
+    splitter: RegexSplitter<'a, 'b>,
+}
+
+impl<'a, 'b> Iterator for OwnedRegexSplitter<'a, 'b> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.splitter.next().map(|r| r.map(<[u8]>::to_vec))
+    }
+}
+
+pub trait LendingIterator {
+    type Item<'a>
+    where
+        Self: 'a;
+
+    fn next<'a>(&'a mut self) -> Option<Self::Item<'a>>;
+}
+
+impl<'a, 'b> LendingIterator for RegexSplitter<'a, 'b> {
+    type Item<'c> = Result<&'c [u8]> where Self: 'c;
+
+    fn next<'c>(&'c mut self) -> Option<Self::Item<'c>> {
+        match self.advance() {
+            None => None,
+            Some(Err(error)) => Some(Err(error)),
+            Some(Ok((record_start, record_end, _))) => {
+                Some(Ok(&self.buffer[record_start..record_end]))
             }
         }
     }
@@ -171,4 +501,256 @@ mod tests {
 
         assert!(splitter.next().is_none());
     }
+
+    #[test]
+    fn test_small_initial_capacity_does_not_panic() {
+        // Regression test: `fill()` used to compare `self.end` against
+        // `Vec::capacity()`, which is only a lower bound on the allocation.
+        // An allocator that rounds a small request up (observed starting at
+        // `capacity == 2`) left `self.end` able to exceed `buffer.len()`,
+        // panicking on the next slice index. Use a non-power-of-two, well
+        // under 8 bytes, since that's exactly the kind of frugal size
+        // `with_capacity`/`with_max_capacity` invite callers to pick.
+        let mut file = tempfile().unwrap();
+        file.write_all(&[b'a'; 200]).unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\s+").unwrap();
+        let mut splitter = RegexSplitter::with_capacity(&mut file, &delimiter, 3);
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(vec![b'a'; 200], r);
+
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_max_capacity_errors_on_oversized_record() {
+        let mut file = tempfile().unwrap();
+        file.write_all(&[b'a'; 4 * SMALL_CAPACITY]).unwrap();
+        file.write_all(b" done").unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\s+").unwrap();
+        let mut splitter = RegexSplitter::with_max_capacity(
+            &mut file,
+            &delimiter,
+            SMALL_CAPACITY,
+            2 * SMALL_CAPACITY,
+        );
+
+        assert!(splitter.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_max_capacity_allows_records_within_bound() {
+        let mut file = tempfile().unwrap();
+        file.write_all(b"hello\n\nworld\n").unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\s+").unwrap();
+        let mut splitter = RegexSplitter::with_max_capacity(
+            &mut file,
+            &delimiter,
+            SMALL_CAPACITY,
+            2 * SMALL_CAPACITY,
+        );
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(b"hello", r);
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(b"world", r);
+
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_owned() {
+        let mut file = tempfile().unwrap();
+        file.write_all(b"hello\n\nworld\n").unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\s+").unwrap();
+        let splitter = RegexSplitter::with_capacity(&mut file, &delimiter, SMALL_CAPACITY);
+
+        let records = splitter
+            .owned()
+            .collect::<std::io::Result<Vec<Vec<u8>>>>()
+            .unwrap();
+        assert_eq!(vec![b"hello".to_vec(), b"world".to_vec()], records);
+    }
+
+    #[test]
+    fn test_next_with_delimiter() {
+        let mut file = tempfile().unwrap();
+        file.write_all(b"hello\n\nworld\n").unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\s+").unwrap();
+        let mut splitter = RegexSplitter::with_capacity(&mut file, &delimiter, SMALL_CAPACITY);
+
+        let (record, d) = splitter.next_with_delimiter().unwrap().unwrap();
+        assert_eq!(b"hello", record);
+        assert_eq!(Some(&b"\n\n"[..]), d);
+
+        let (record, d) = splitter.next_with_delimiter().unwrap().unwrap();
+        assert_eq!(b"world", record);
+        assert_eq!(Some(&b"\n"[..]), d);
+
+        assert!(splitter.next_with_delimiter().is_none());
+    }
+
+    #[test]
+    fn test_next_with_captures() {
+        let mut file = tempfile().unwrap();
+        file.write_all(b"hello--1world--2").unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"--(\d)").unwrap();
+        let mut splitter = RegexSplitter::with_capacity(&mut file, &delimiter, SMALL_CAPACITY);
+
+        let (record, captures) = splitter.next_with_captures().unwrap().unwrap();
+        assert_eq!(b"hello", record);
+        assert_eq!(b"1", &captures.unwrap()[1]);
+
+        let (record, captures) = splitter.next_with_captures().unwrap().unwrap();
+        assert_eq!(b"world", record);
+        assert_eq!(b"2", &captures.unwrap()[1]);
+
+        assert!(splitter.next_with_captures().is_none());
+    }
+
+    #[test]
+    fn test_next_with_captures_context_sensitive_delimiter() {
+        let mut file = tempfile().unwrap();
+        file.write_all(b"hello,world").unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\b,").unwrap();
+        let mut splitter = RegexSplitter::with_capacity(&mut file, &delimiter, SMALL_CAPACITY);
+
+        let (record, captures) = splitter.next_with_captures().unwrap().unwrap();
+        assert_eq!(b"hello", record);
+        assert!(captures.is_some());
+
+        let (record, captures) = splitter.next_with_captures().unwrap().unwrap();
+        assert_eq!(b"world", record);
+        assert!(captures.is_none());
+
+        assert!(splitter.next_with_captures().is_none());
+    }
+
+    #[test]
+    fn test_large_undelimited_record() {
+        // Regression test: `advance` used to finalize a "no match found"
+        // search as the last record whenever `find` failed, without
+        // checking `self.eof`. That truncated any record spanning more
+        // than one buffer-full when no delimiter ever appeared, instead of
+        // continuing to grow the buffer and accumulate more data.
+        let mut file = tempfile().unwrap();
+        file.write_all(&[b'a'; 4 * SMALL_CAPACITY]).unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\s+").unwrap();
+        let mut splitter = RegexSplitter::with_capacity(&mut file, &delimiter, SMALL_CAPACITY);
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(vec![b'a'; 4 * SMALL_CAPACITY], r);
+
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_max_delimiter_len_large_record() {
+        let mut file = tempfile().unwrap();
+        file.write_all(&[b'a'; 10 * SMALL_CAPACITY]).unwrap();
+        file.write_all(b"\n\n").unwrap();
+        file.write_all(&[b'b'; 10 * SMALL_CAPACITY]).unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\s+").unwrap();
+        let mut splitter =
+            RegexSplitter::with_max_delimiter_len(&mut file, &delimiter, SMALL_CAPACITY, 2);
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(&[b'a'; 10 * SMALL_CAPACITY][..], r);
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(&[b'b'; 10 * SMALL_CAPACITY][..], r);
+
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_max_delimiter_len_straddles_buffer() {
+        let spaces = [b' '; SMALL_CAPACITY];
+
+        let mut file = tempfile().unwrap();
+        file.write_all(b"greetings").unwrap();
+        file.write_all(&spaces).unwrap();
+        file.write_all(b"world").unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\s+").unwrap();
+        let mut splitter = RegexSplitter::with_max_delimiter_len(
+            &mut file,
+            &delimiter,
+            SMALL_CAPACITY,
+            SMALL_CAPACITY,
+        );
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(b"greetings", r);
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(b"world", r);
+
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_builder_skip_empty() {
+        use crate::RegexSplitterBuilder;
+
+        let mut file = tempfile().unwrap();
+        file.write_all(b"  hello   world  ").unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\s+").unwrap();
+        let mut splitter = RegexSplitterBuilder::new()
+            .capacity(SMALL_CAPACITY)
+            .skip_empty(true)
+            .build(&mut file, &delimiter);
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(b"hello", r);
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(b"world", r);
+
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_builder_without_skip_empty_yields_empty_records() {
+        use crate::RegexSplitterBuilder;
+
+        let mut file = tempfile().unwrap();
+        file.write_all(b" hello").unwrap();
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let delimiter = Regex::new(r"\s+").unwrap();
+        let mut splitter = RegexSplitterBuilder::new()
+            .capacity(SMALL_CAPACITY)
+            .build(&mut file, &delimiter);
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(b"", r);
+
+        let r = splitter.next().unwrap().unwrap();
+        assert_eq!(b"hello", r);
+
+        assert!(splitter.next().is_none());
+    }
 }